@@ -0,0 +1,151 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A block within `Message.content`, matching Anthropic's multimodal content
+/// format: https://docs.anthropic.com/en/docs/build-with-claude/vision
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub(crate) enum ContentBlock {
+    Text { text: String },
+    Image { source: ImageSource },
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) struct ImageSource {
+    #[serde(rename = "type")]
+    pub(crate) source_type: String,
+    pub(crate) media_type: String,
+    pub(crate) data: String,
+}
+
+const SUPPORTED_MEDIA_TYPES: [&str; 4] = ["image/jpeg", "image/png", "image/gif", "image/webp"];
+
+/// A path's last-seen mtime/size, mapping it to the cached content hash so a
+/// re-sent attachment can be recognized without re-reading and re-hashing it.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct CacheEntry {
+    mtime_secs: u64,
+    size: u64,
+    hash: String,
+}
+
+fn cache_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = dirs::data_dir()
+        .ok_or("Could not find data directory")?
+        .join("anth")
+        .join("attachment_cache");
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+fn index_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(cache_dir()?.join("index.json"))
+}
+
+fn load_index() -> HashMap<String, CacheEntry> {
+    let Ok(path) = index_path() else {
+        return HashMap::new();
+    };
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_index(index: &HashMap<String, CacheEntry>) -> Result<(), Box<dyn std::error::Error>> {
+    let path = index_path()?;
+    fs::write(path, serde_json::to_string_pretty(index)?)?;
+    Ok(())
+}
+
+/// Reads `path`, base64-encodes it, and derives the media type from the file
+/// extension via `mime_guess`. Rejects anything Claude's vision API doesn't
+/// accept (jpeg, png, gif, webp).
+///
+/// Re-encoding is skipped for a path that hasn't changed since it was last
+/// attached: a `sha2` hash of the file's contents is used as a cache key
+/// under the data dir, and an index keyed by path + mtime + size lets a
+/// repeat `load_image_block` call for the same file skip the disk read
+/// entirely and reuse the cached, already-encoded block.
+pub(crate) fn load_image_block(path: &Path) -> Result<ContentBlock, Box<dyn std::error::Error>> {
+    let metadata = fs::metadata(path)?;
+    let mtime_secs = metadata
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let size = metadata.len();
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    let path_key = canonical.to_string_lossy().to_string();
+
+    let mut index = load_index();
+
+    if let Some(entry) = index.get(&path_key) {
+        if entry.mtime_secs == mtime_secs && entry.size == size {
+            if let Some(block) = load_cached_block(&entry.hash) {
+                return Ok(block);
+            }
+        }
+    }
+
+    let media_type = mime_guess::from_path(path)
+        .first_raw()
+        .unwrap_or("application/octet-stream")
+        .to_string();
+
+    if !SUPPORTED_MEDIA_TYPES.contains(&media_type.as_str()) {
+        return Err(format!(
+            "Unsupported attachment type '{}' for {}. Supported types: {}",
+            media_type,
+            path.display(),
+            SUPPORTED_MEDIA_TYPES.join(", ")
+        )
+        .into());
+    }
+
+    let bytes = fs::read(path)?;
+    let hash = format!("{:x}", Sha256::digest(&bytes));
+    let data = STANDARD.encode(&bytes);
+
+    let block = ContentBlock::Image {
+        source: ImageSource {
+            source_type: "base64".to_string(),
+            media_type,
+            data,
+        },
+    };
+
+    save_cached_block(&hash, &block)?;
+    index.insert(
+        path_key,
+        CacheEntry {
+            mtime_secs,
+            size,
+            hash,
+        },
+    );
+    save_index(&index)?;
+
+    Ok(block)
+}
+
+fn load_cached_block(hash: &str) -> Option<ContentBlock> {
+    let path = cache_dir().ok()?.join(format!("{}.json", hash));
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn save_cached_block(hash: &str, block: &ContentBlock) -> Result<(), Box<dyn std::error::Error>> {
+    let path = cache_dir()?.join(format!("{}.json", hash));
+    fs::write(path, serde_json::to_string(block)?)?;
+    Ok(())
+}
@@ -0,0 +1,144 @@
+use crate::{get_config_path, session, AnthropicClient, Message};
+use futures_util::StreamExt;
+use irc::client::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+
+/// IRC messages are limited to 512 bytes including the `PRIVMSG <target> :`
+/// prefix and trailing CRLF; stay well under that for the text portion alone.
+const IRC_LINE_LIMIT: usize = 400;
+
+/// Session name under which a channel's (or nick's) history is persisted, so
+/// it doesn't collide with sessions created via `anth start`/`anth gen`.
+fn bot_session_name(reply_to: &str) -> String {
+    format!("bot-{}", reply_to)
+}
+
+/// Splits `text` into chunks no longer than `IRC_LINE_LIMIT` bytes, first on
+/// existing newlines and then, for any line still too long, on UTF-8 char
+/// boundaries, so a single long reply becomes multiple `PRIVMSG`s instead of
+/// one over-long line. An empty/whitespace-only `text` still yields one
+/// placeholder chunk, so the bot always sends something rather than looking
+/// hung.
+fn split_for_irc(text: &str) -> Vec<String> {
+    let mut chunks = Vec::new();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let mut rest = line;
+        while !rest.is_empty() {
+            if rest.len() <= IRC_LINE_LIMIT {
+                chunks.push(rest.to_string());
+                break;
+            }
+
+            let mut split_at = IRC_LINE_LIMIT;
+            while !rest.is_char_boundary(split_at) {
+                split_at -= 1;
+            }
+            chunks.push(rest[..split_at].to_string());
+            rest = &rest[split_at..];
+        }
+    }
+
+    if chunks.is_empty() {
+        chunks.push("(no response)".to_string());
+    }
+
+    chunks
+}
+
+/// Bot-specific settings read from the same `anth/config.json` used for the
+/// API key. Kept separate from the `api_key`-only `HashMap` so existing
+/// config files without these keys keep working untouched.
+#[derive(Debug, Deserialize)]
+struct BotConfig {
+    server: String,
+    #[serde(default = "default_port")]
+    port: u16,
+    nickname: String,
+    channels: Vec<String>,
+    #[serde(default = "default_prefix")]
+    command_prefix: String,
+}
+
+fn default_port() -> u16 {
+    6667
+}
+
+fn default_prefix() -> String {
+    "u!".to_string()
+}
+
+/// Connects to the configured IRC server and answers messages that start
+/// with `command_prefix` by forwarding the rest of the line to Claude.
+/// Conversation history is kept per-channel (or per-nick for DMs) so each
+/// conversation has its own context.
+pub(crate) async fn run_bot() -> Result<(), Box<dyn std::error::Error>> {
+    let config_path = get_config_path()?;
+    let config_content = fs::read_to_string(&config_path).map_err(|_| {
+        format!(
+            "Could not read bot config at {}. Add \"server\", \"nickname\", \"channels\" and \"command_prefix\" to it.",
+            config_path.display()
+        )
+    })?;
+    let config: BotConfig = serde_json::from_str(&config_content)?;
+
+    let irc_config = Config {
+        nickname: Some(config.nickname.clone()),
+        server: Some(config.server.clone()),
+        port: Some(config.port),
+        channels: config.channels.clone(),
+        use_tls: Some(false),
+        ..Config::default()
+    };
+
+    let anthropic = AnthropicClient::new()?;
+    let mut client = Client::from_config(irc_config).await?;
+    client.identify()?;
+
+    let mut stream = client.stream()?;
+    let mut histories: HashMap<String, Vec<Message>> = HashMap::new();
+
+    while let Some(message) = stream.next().await.transpose()? {
+        let Command::PRIVMSG(ref target, ref text) = message.command else {
+            continue;
+        };
+
+        let Some(question) = text.strip_prefix(&config.command_prefix) else {
+            continue;
+        };
+        let question = question.trim();
+        if question.is_empty() {
+            continue;
+        }
+
+        let reply_to = message
+            .response_target()
+            .unwrap_or(target)
+            .to_string();
+
+        let session_name = bot_session_name(&reply_to);
+        let history = histories
+            .entry(reply_to.clone())
+            .or_insert_with(|| session::load(&session_name));
+        history.push(Message::text("user", question));
+
+        match anthropic.send_message(history.clone()).await {
+            Ok(response) => {
+                history.push(Message::text("assistant", response.clone()));
+                if let Err(e) = session::save(&session_name, history) {
+                    eprintln!("anth bot: failed to save history for '{}': {}", reply_to, e);
+                }
+                for chunk in split_for_irc(&response) {
+                    client.send_privmsg(&reply_to, chunk)?;
+                }
+            }
+            Err(e) => {
+                history.pop();
+                client.send_privmsg(&reply_to, format!("Error: {}", e))?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,193 @@
+use crate::{session, AnthropicClient, Message};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::Mutex;
+
+pub(crate) fn socket_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = dirs::data_dir()
+        .ok_or("Could not find data directory")?
+        .join("anth");
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+
+    Ok(dir.join("daemon.sock"))
+}
+
+/// One line-delimited JSON request read from the daemon's Unix socket.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum DaemonRequest {
+    Send { session: String, message: String },
+    ListSessions,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+struct DaemonResponse {
+    ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sessions: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+type Histories = Arc<Mutex<HashMap<String, Vec<Message>>>>;
+
+/// Runs `anth daemon`: a long-lived process that keeps session histories and
+/// the `reqwest::Client` warm, and serves `anth start`/`anth gen` clients
+/// over a local Unix socket using line-delimited JSON requests/responses.
+pub(crate) async fn run_daemon() -> Result<(), Box<dyn std::error::Error>> {
+    let socket_path = socket_path()?;
+    if socket_path.exists() {
+        fs::remove_file(&socket_path)?;
+    }
+
+    let client = Arc::new(AnthropicClient::new()?);
+    let histories: Histories = Arc::new(Mutex::new(HashMap::new()));
+
+    let listener = UnixListener::bind(&socket_path)?;
+    println!("anth daemon listening on {}", socket_path.display());
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let client = Arc::clone(&client);
+        let histories = Arc::clone(&histories);
+
+        tokio::spawn(async move {
+            if let Err(e) = handle_connection(stream, client, histories).await {
+                eprintln!("anth daemon: connection error: {}", e);
+            }
+        });
+    }
+}
+
+async fn handle_connection(
+    stream: tokio::net::UnixStream,
+    client: Arc<AnthropicClient>,
+    histories: Histories,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Some(line) = lines.next_line().await? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<DaemonRequest>(&line) {
+            Ok(DaemonRequest::Send { session: session_name, message }) => {
+                handle_send(&client, &histories, session_name, message).await
+            }
+            Ok(DaemonRequest::ListSessions) => match session::list() {
+                Ok(sessions) => DaemonResponse {
+                    ok: true,
+                    sessions: Some(sessions),
+                    ..Default::default()
+                },
+                Err(e) => DaemonResponse {
+                    ok: false,
+                    error: Some(e.to_string()),
+                    ..Default::default()
+                },
+            },
+            Err(e) => DaemonResponse {
+                ok: false,
+                error: Some(format!("invalid request: {}", e)),
+                ..Default::default()
+            },
+        };
+
+        let mut payload = serde_json::to_string(&response)?;
+        payload.push('\n');
+        writer.write_all(payload.as_bytes()).await?;
+    }
+
+    Ok(())
+}
+
+async fn handle_send(
+    client: &AnthropicClient,
+    histories: &Histories,
+    session_name: String,
+    message: String,
+) -> DaemonResponse {
+    // Clone this session's history out and drop the lock before the network
+    // call, so a slow in-flight request for one session doesn't block every
+    // other session's requests from being served concurrently.
+    let mut history = {
+        let mut histories = histories.lock().await;
+        let history = histories
+            .entry(session_name.clone())
+            .or_insert_with(|| session::load(&session_name));
+        history.push(Message::text("user", message));
+        history.clone()
+    };
+
+    match client.send_message(history.clone()).await {
+        Ok(text) => {
+            history.push(Message::text("assistant", text.clone()));
+            if let Err(e) = session::save(&session_name, &history) {
+                eprintln!("anth daemon: failed to save session '{}': {}", session_name, e);
+            }
+
+            let mut histories = histories.lock().await;
+            histories.insert(session_name, history);
+
+            DaemonResponse {
+                ok: true,
+                response: Some(text),
+                ..Default::default()
+            }
+        }
+        Err(e) => {
+            // Roll back the user message we optimistically recorded above.
+            let mut histories = histories.lock().await;
+            if let Some(history) = histories.get_mut(&session_name) {
+                history.pop();
+            }
+
+            DaemonResponse {
+                ok: false,
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    }
+}
+
+/// Tries to reach a running `anth daemon` over its Unix socket and have it
+/// answer `message` using its warm client and in-memory history for
+/// `session_name`. Returns `None` when no daemon is listening, so callers
+/// fall back to calling the API directly; returns `Some(Ok(text))` or
+/// `Some(Err(..))` once the daemon has actually answered.
+pub(crate) async fn try_send_via_daemon(session_name: &str, message: &str) -> Option<Result<String, String>> {
+    let socket_path = socket_path().ok()?;
+    let stream = UnixStream::connect(&socket_path).await.ok()?;
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    let request = DaemonRequest::Send {
+        session: session_name.to_string(),
+        message: message.to_string(),
+    };
+    let mut payload = serde_json::to_string(&request).ok()?;
+    payload.push('\n');
+    writer.write_all(payload.as_bytes()).await.ok()?;
+
+    let line = lines.next_line().await.ok()??;
+    let response: DaemonResponse = serde_json::from_str(&line).ok()?;
+
+    Some(if response.ok {
+        Ok(response.response.unwrap_or_default())
+    } else {
+        Err(response.error.unwrap_or_else(|| "daemon request failed".to_string()))
+    })
+}
@@ -1,15 +1,25 @@
 use clap::{Parser, Subcommand};
 use colored::*;
+use futures_util::StreamExt;
 use rustyline::error::ReadlineError;
 use rustyline::Editor;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::env;
 use std::fs;
+use std::io::Write;
 use std::path::PathBuf;
 use std::process::Command;
 use tokio;
 
+mod attachments;
+mod bot;
+mod daemon;
+mod serve;
+mod session;
+
+use attachments::ContentBlock;
+
 #[derive(Parser)]
 #[command(name = "anth")]
 #[command(about = "Anthropic CLI tool for chatting and generating content")]
@@ -21,20 +31,77 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     /// Start an interactive chat session
-    Start,
+    Start {
+        /// Named session to load and save history under
+        #[arg(long, default_value = session::DEFAULT_SESSION)]
+        session: String,
+    },
     /// Generate a response to a message
     Gen {
         /// The message to send
         message: String,
+        /// Path to an image to attach alongside the message
+        #[arg(long)]
+        file: Option<PathBuf>,
+        /// Named session to load and save history under
+        #[arg(long, default_value = session::DEFAULT_SESSION)]
+        session: String,
     },
     /// Generate a commit message from git diff
-    Commit,
+    Commit {
+        /// Open the suggested message in $EDITOR and commit it
+        #[arg(long)]
+        apply: bool,
+        /// Scope hint included in the prompt to Claude
+        #[arg(long)]
+        scope: Option<String>,
+        /// Pass --amend through to the underlying `git commit`
+        #[arg(long)]
+        amend: bool,
+    },
+    /// Run as an IRC bot, answering prefixed commands in configured channels
+    Bot,
+    /// Manage saved conversation sessions
+    Sessions {
+        #[command(subcommand)]
+        action: SessionCommands,
+    },
+    /// Run a background daemon that keeps sessions and the HTTP client warm
+    Daemon,
+    /// Run a webhook server that reviews pushed commits
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8787)]
+        port: u16,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionCommands {
+    /// List saved sessions
+    List,
+    /// Remove a saved session
+    Rm {
+        /// Name of the session to remove
+        name: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
-struct Message {
-    role: String,
-    content: String,
+pub(crate) struct Message {
+    pub(crate) role: String,
+    pub(crate) content: Vec<ContentBlock>,
+}
+
+impl Message {
+    /// Build a plain text message, the common case for chat, bot replies,
+    /// and commit-message prompts.
+    pub(crate) fn text(role: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            role: role.into(),
+            content: vec![ContentBlock::Text { text: text.into() }],
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -42,6 +109,8 @@ struct AnthropicRequest {
     model: String,
     max_tokens: u32,
     messages: Vec<Message>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -54,14 +123,31 @@ struct Content {
     text: String,
 }
 
-struct AnthropicClient {
+/// A single Server-Sent Event payload from a streaming `/v1/messages` response.
+/// Only the `content_block_delta` case carries text we care about; everything
+/// else (`message_start`, `ping`, `message_stop`, ...) is ignored.
+#[derive(Debug, Deserialize)]
+struct StreamEvent {
+    #[serde(rename = "type")]
+    event_type: String,
+    #[serde(default)]
+    delta: Option<StreamDelta>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamDelta {
+    #[serde(default)]
+    text: String,
+}
+
+pub(crate) struct AnthropicClient {
     client: reqwest::Client,
     api_key: String,
     base_url: String,
 }
 
 impl AnthropicClient {
-    fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    pub(crate) fn new() -> Result<Self, Box<dyn std::error::Error>> {
         let api_key = env::var("ANTHROPIC_API_KEY")
             .or_else(|_| {
                 // Try to read from config file
@@ -87,11 +173,12 @@ impl AnthropicClient {
         })
     }
 
-    async fn send_message(&self, messages: Vec<Message>) -> Result<String, Box<dyn std::error::Error>> {
+    pub(crate) async fn send_message(&self, messages: Vec<Message>) -> Result<String, Box<dyn std::error::Error>> {
         let request = AnthropicRequest {
             model: "claude-3-sonnet-20240229".to_string(),
             max_tokens: 1000,
             messages,
+            stream: None,
         };
 
         let response = self
@@ -112,9 +199,77 @@ impl AnthropicClient {
         let response_data: AnthropicResponse = response.json().await?;
         Ok(response_data.content.first().map(|c| c.text.clone()).unwrap_or_default())
     }
+
+    /// Same request as `send_message`, but with `"stream": true` set so the
+    /// response arrives as Server-Sent Events. Each `content_block_delta`
+    /// chunk is printed as soon as it arrives instead of waiting for the
+    /// full message, and the accumulated text is returned so callers can
+    /// still save it to chat history.
+    async fn send_message_stream(&self, messages: Vec<Message>) -> Result<String, Box<dyn std::error::Error>> {
+        let request = AnthropicRequest {
+            model: "claude-3-sonnet-20240229".to_string(),
+            max_tokens: 1000,
+            messages,
+            stream: Some(true),
+        };
+
+        let response = self
+            .client
+            .post(&self.base_url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .header("content-type", "application/json")
+            .json(&request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(format!("API request failed: {}", error_text).into());
+        }
+
+        let mut stream = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut full_text = String::new();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(newline_pos) = buffer.iter().position(|&b| b == b'\n') {
+                // Raw `\n` bytes only ever appear as SSE line separators (JSON
+                // escapes embedded newlines), so splitting here never cuts a
+                // multi-byte UTF-8 character in half the way decoding each
+                // chunk independently would.
+                let line_bytes: Vec<u8> = buffer.drain(..=newline_pos).collect();
+                let line = String::from_utf8_lossy(&line_bytes[..line_bytes.len() - 1])
+                    .trim_end_matches('\r')
+                    .to_string();
+
+                let Some(data) = line.strip_prefix("data: ") else {
+                    continue;
+                };
+
+                let Ok(event) = serde_json::from_str::<StreamEvent>(data) else {
+                    continue;
+                };
+
+                if event.event_type == "content_block_delta" {
+                    if let Some(delta) = event.delta {
+                        print!("{}", delta.text);
+                        std::io::stdout().flush()?;
+                        full_text.push_str(&delta.text);
+                    }
+                }
+            }
+        }
+        println!();
+
+        Ok(full_text)
+    }
 }
 
-fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+pub(crate) fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let config_dir = dirs::config_dir()
         .ok_or("Could not find config directory")?
         .join("anth");
@@ -126,38 +281,6 @@ fn get_config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
     Ok(config_dir.join("config.json"))
 }
 
-fn get_chat_history_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let data_dir = dirs::data_dir()
-        .ok_or("Could not find data directory")?
-        .join("anth");
-    
-    if !data_dir.exists() {
-        fs::create_dir_all(&data_dir)?;
-    }
-    
-    Ok(data_dir.join("chat_history.json"))
-}
-
-fn load_chat_history() -> Vec<Message> {
-    let history_path = match get_chat_history_path() {
-        Ok(path) => path,
-        Err(_) => return Vec::new(),
-    };
-
-    if let Ok(content) = fs::read_to_string(history_path) {
-        serde_json::from_str(&content).unwrap_or_default()
-    } else {
-        Vec::new()
-    }
-}
-
-fn save_chat_history(messages: &[Message]) -> Result<(), Box<dyn std::error::Error>> {
-    let history_path = get_chat_history_path()?;
-    let content = serde_json::to_string_pretty(messages)?;
-    fs::write(history_path, content)?;
-    Ok(())
-}
-
 fn get_git_diff() -> Result<String, Box<dyn std::error::Error>> {
     let output = Command::new("git")
         .args(["diff", "--cached"])
@@ -179,14 +302,21 @@ fn get_git_diff() -> Result<String, Box<dyn std::error::Error>> {
     }
 }
 
-async fn start_chat() -> Result<(), Box<dyn std::error::Error>> {
-    let client = AnthropicClient::new()?;
+async fn start_chat(session_name: String) -> Result<(), Box<dyn std::error::Error>> {
+    // Deferred until the daemon turns out not to be running (or a turn needs
+    // an attachment): a daemon holds its own authenticated client, so a
+    // terminal that only ever talks through the daemon shouldn't need a
+    // local ANTHROPIC_API_KEY at all.
+    let mut client: Option<AnthropicClient> = None;
     let mut rl: Editor<(), rustyline::FileHistory> = Editor::new()?;
-    let mut messages = load_chat_history();
+    let mut messages = session::load(&session_name);
+    let mut pending_attachments: Vec<ContentBlock> = Vec::new();
 
     println!("{}", "Welcome to Anthropic CLI Chat!".green().bold());
+    println!("Session: {}", session_name.cyan());
     println!("Type 'quit' or 'exit' to end the session.");
     println!("Type 'clear' to clear chat history.");
+    println!("Type '/attach <path>' to attach an image to your next message.");
     println!();
 
     loop {
@@ -206,40 +336,87 @@ async fn start_chat() -> Result<(), Box<dyn std::error::Error>> {
                     }
                     "clear" => {
                         messages.clear();
-                        save_chat_history(&messages)?;
+                        session::save(&session_name, &messages)?;
                         println!("{}", "Chat history cleared.".yellow());
                         continue;
                     }
+                    s if s.starts_with("/attach ") => {
+                        let path = PathBuf::from(s["/attach ".len()..].trim());
+                        match attachments::load_image_block(&path) {
+                            Ok(block) => {
+                                pending_attachments.push(block);
+                                println!(
+                                    "{}",
+                                    format!("Attached {} (will be sent with your next message).", path.display())
+                                        .yellow()
+                                );
+                            }
+                            Err(e) => println!("{}", format!("Error: {}", e).red()),
+                        }
+                        continue;
+                    }
                     _ => {
-                        // Add user message
+                        let has_attachments = !pending_attachments.is_empty();
+
+                        // Add user message, along with any pending attachments
+                        let mut content = vec![ContentBlock::Text { text: line.to_string() }];
+                        content.extend(pending_attachments.drain(..));
                         messages.push(Message {
                             role: "user".to_string(),
-                            content: line.to_string(),
+                            content,
                         });
 
                         print!("{}", "Claude: ".blue().bold());
-                        
-                        // Send to API
-                        match client.send_message(messages.clone()).await {
-                            Ok(response) => {
+                        std::io::stdout().flush()?;
+
+                        // A running `anth daemon` keeps this session's history and
+                        // the API client warm across terminals; prefer it for
+                        // plain-text turns and only fall back to calling the API
+                        // directly when it isn't running or this turn carries an
+                        // attachment (outside the daemon's wire protocol).
+                        let daemon_result = if has_attachments {
+                            None
+                        } else {
+                            daemon::try_send_via_daemon(&session_name, line).await
+                        };
+
+                        match daemon_result {
+                            Some(Ok(response)) => {
+                                // The daemon's wire protocol returns one full reply rather
+                                // than a token stream, so say so instead of silently
+                                // dropping chunk0-1's token-by-token output.
+                                print!("{}", "(daemon, non-streaming) ".dimmed());
                                 println!("{}", response);
-                                
-                                // Add assistant response
-                                messages.push(Message {
-                                    role: "assistant".to_string(),
-                                    content: response,
-                                });
-                                
-                                // Save history
-                                if let Err(e) = save_chat_history(&messages) {
-                                    eprintln!("Warning: Failed to save chat history: {}", e);
-                                }
+                                messages.push(Message::text("assistant", response));
                             }
-                            Err(e) => {
+                            Some(Err(e)) => {
                                 println!("{}", format!("Error: {}", e).red());
-                                // Remove the user message if API call failed
                                 messages.pop();
                             }
+                            None => {
+                                if client.is_none() {
+                                    client = Some(AnthropicClient::new()?);
+                                }
+                                let client = client.as_ref().unwrap();
+
+                                // Send to API, streaming the response as it arrives
+                                match client.send_message_stream(messages.clone()).await {
+                                    Ok(response) => {
+                                        // Add assistant response
+                                        messages.push(Message::text("assistant", response));
+
+                                        // Save history
+                                        if let Err(e) = session::save(&session_name, &messages) {
+                                            eprintln!("Warning: Failed to save chat history: {}", e);
+                                        }
+                                    }
+                                    Err(e) => {
+                                        println!("{}", format!("Error: {}", e).red());
+                                        // Remove the user message if API call failed
+                                        messages.pop();
+                                    }
+                                }
+                            }
                         }
                     }
                 }
@@ -262,17 +439,53 @@ async fn start_chat() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn generate_message(message: String) -> Result<(), Box<dyn std::error::Error>> {
+async fn generate_message(
+    message: String,
+    file: Option<PathBuf>,
+    session_name: String,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // A plain-text message can be handled by a running `anth daemon`, which
+    // keeps the API client and this session's history warm across terminals.
+    // Attachments aren't part of the daemon's wire protocol, so those always
+    // go straight to the API.
+    if file.is_none() {
+        if let Some(result) = daemon::try_send_via_daemon(&session_name, &message).await {
+            return match result {
+                Ok(response) => {
+                    // The daemon returns one full reply rather than a token
+                    // stream, so say so instead of silently dropping
+                    // chunk0-1's token-by-token output.
+                    print!("{}", "(daemon, non-streaming) ".dimmed());
+                    println!("{}", response);
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("{}", format!("Error: {}", e).red());
+                    std::process::exit(1);
+                }
+            };
+        }
+    }
+
     let client = AnthropicClient::new()?;
-    
-    let messages = vec![Message {
+
+    let mut content = vec![ContentBlock::Text { text: message }];
+    if let Some(path) = file {
+        content.push(attachments::load_image_block(&path)?);
+    }
+
+    let mut messages = session::load(&session_name);
+    messages.push(Message {
         role: "user".to_string(),
-        content: message,
-    }];
+        content,
+    });
 
-    match client.send_message(messages).await {
+    match client.send_message_stream(messages.clone()).await {
         Ok(response) => {
-            println!("{}", response);
+            messages.push(Message::text("assistant", response));
+            if let Err(e) = session::save(&session_name, &messages) {
+                eprintln!("Warning: Failed to save session '{}': {}", session_name, e);
+            }
         }
         Err(e) => {
             eprintln!("{}", format!("Error: {}", e).red());
@@ -283,38 +496,137 @@ async fn generate_message(message: String) -> Result<(), Box<dyn std::error::Err
     Ok(())
 }
 
-async fn generate_commit_message() -> Result<(), Box<dyn std::error::Error>> {
+fn list_sessions() -> Result<(), Box<dyn std::error::Error>> {
+    let sessions = session::list()?;
+    if sessions.is_empty() {
+        println!("No saved sessions.");
+    } else {
+        for name in sessions {
+            println!("{}", name);
+        }
+    }
+    Ok(())
+}
+
+fn remove_session(name: String) -> Result<(), Box<dyn std::error::Error>> {
+    session::remove(&name)?;
+    println!("{}", format!("Removed session '{}'.", name).yellow());
+    Ok(())
+}
+
+const DEFAULT_COMMIT_PROMPT_TEMPLATE: &str = "Please generate a concise and descriptive commit message for the following git diff. \
+The commit message should follow conventional commit format and be clear about what changes were made:\n\n{diff}";
+
+/// Reads a team-specific prompt template from `commit_prompt_template` in
+/// `anth/config.json`, if one is set. The template must contain a `{diff}`
+/// placeholder; falls back to `DEFAULT_COMMIT_PROMPT_TEMPLATE` otherwise.
+fn load_commit_prompt_template() -> Option<String> {
+    let config_path = get_config_path().ok()?;
+    let config_content = fs::read_to_string(config_path).ok()?;
+    let config: HashMap<String, String> = serde_json::from_str(&config_content).ok()?;
+    config
+        .get("commit_prompt_template")
+        .filter(|template| template.contains("{diff}"))
+        .cloned()
+}
+
+fn build_commit_prompt(diff: &str, scope: Option<&str>) -> String {
+    let template = load_commit_prompt_template().unwrap_or_else(|| DEFAULT_COMMIT_PROMPT_TEMPLATE.to_string());
+    let prompt = template.replace("{diff}", diff);
+
+    match scope {
+        Some(scope) => format!("Scope hint: {}\n\n{}", scope, prompt),
+        None => prompt,
+    }
+}
+
+/// Writes `initial` to a temp file, opens it in `$EDITOR` (falling back to
+/// `vi`), and returns the edited contents once the editor exits.
+fn edit_in_editor(initial: &str) -> Result<String, Box<dyn std::error::Error>> {
+    let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
+
+    let mut path = env::temp_dir();
+    path.push(format!("anth-commit-{}.txt", std::process::id()));
+    fs::write(&path, initial)?;
+
+    let status = Command::new(&editor).arg(&path).status()?;
+    if !status.success() {
+        fs::remove_file(&path).ok();
+        return Err(format!("{} exited with a non-zero status", editor).into());
+    }
+
+    let edited = fs::read_to_string(&path)?;
+    fs::remove_file(&path).ok();
+    Ok(edited)
+}
+
+/// Runs `git commit -F -`, feeding `message` on stdin, and `--amend` when
+/// requested.
+fn apply_commit(message: &str, amend: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let mut args = vec!["commit", "-F", "-"];
+    if amend {
+        args.push("--amend");
+    }
+
+    let mut child = Command::new("git")
+        .args(&args)
+        .stdin(std::process::Stdio::piped())
+        .spawn()?;
+
+    child
+        .stdin
+        .take()
+        .ok_or("Failed to open git commit stdin")?
+        .write_all(message.as_bytes())?;
+
+    let status = child.wait()?;
+    if !status.success() {
+        return Err("git commit failed".into());
+    }
+
+    Ok(())
+}
+
+async fn generate_commit_message(
+    apply: bool,
+    scope: Option<String>,
+    amend: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     let diff = get_git_diff()?;
-    
+
     if diff.trim().is_empty() {
         eprintln!("{}", "No git changes found. Please stage some changes first.".red());
         std::process::exit(1);
     }
 
     let client = AnthropicClient::new()?;
-    
-    let prompt = format!(
-        "Please generate a concise and descriptive commit message for the following git diff. \
-         The commit message should follow conventional commit format and be clear about what changes were made:\n\n{}",
-        diff
-    );
-
-    let messages = vec![Message {
-        role: "user".to_string(),
-        content: prompt,
-    }];
+    let prompt = build_commit_prompt(&diff, scope.as_deref());
+    let messages = vec![Message::text("user", prompt)];
 
-    match client.send_message(messages).await {
-        Ok(response) => {
-            println!("{}", "Suggested commit message:".green().bold());
-            println!("{}", response.trim());
-        }
+    let suggestion = match client.send_message(messages).await {
+        Ok(response) => response.trim().to_string(),
         Err(e) => {
             eprintln!("{}", format!("Error: {}", e).red());
             std::process::exit(1);
         }
+    };
+
+    println!("{}", "Suggested commit message:".green().bold());
+    println!("{}", suggestion);
+
+    if !apply {
+        return Ok(());
+    }
+
+    let message = edit_in_editor(&suggestion)?;
+    if message.trim().is_empty() {
+        eprintln!("{}", "Empty commit message, aborting.".yellow());
+        return Ok(());
     }
 
+    apply_commit(&message, amend)?;
+    println!("{}", "Committed.".green().bold());
+
     Ok(())
 }
 
@@ -325,14 +637,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
-        Commands::Start => {
-            start_chat().await?;
+        Commands::Start { session } => {
+            start_chat(session).await?;
+        }
+        Commands::Gen { message, file, session } => {
+            generate_message(message, file, session).await?;
+        }
+        Commands::Commit { apply, scope, amend } => {
+            generate_commit_message(apply, scope, amend).await?;
+        }
+        Commands::Bot => {
+            bot::run_bot().await?;
         }
-        Commands::Gen { message } => {
-            generate_message(message).await?;
+        Commands::Sessions { action } => match action {
+            SessionCommands::List => list_sessions()?,
+            SessionCommands::Rm { name } => remove_session(name)?,
+        },
+        Commands::Daemon => {
+            daemon::run_daemon().await?;
         }
-        Commands::Commit => {
-            generate_commit_message().await?;
+        Commands::Serve { port } => {
+            serve::run_server(port).await?;
         }
     }
 
@@ -0,0 +1,71 @@
+use crate::Message;
+use std::fs;
+use std::path::PathBuf;
+
+/// Session used by `start`/`gen` when `--session` is not given.
+pub(crate) const DEFAULT_SESSION: &str = "default";
+
+fn sessions_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let dir = dirs::data_dir()
+        .ok_or("Could not find data directory")?
+        .join("anth")
+        .join("sessions");
+
+    if !dir.exists() {
+        fs::create_dir_all(&dir)?;
+    }
+
+    Ok(dir)
+}
+
+pub(crate) fn session_path(name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    Ok(sessions_dir()?.join(format!("{}.json", name)))
+}
+
+pub(crate) fn load(name: &str) -> Vec<Message> {
+    let history_path = match session_path(name) {
+        Ok(path) => path,
+        Err(_) => return Vec::new(),
+    };
+
+    if let Ok(content) = fs::read_to_string(history_path) {
+        serde_json::from_str(&content).unwrap_or_default()
+    } else {
+        Vec::new()
+    }
+}
+
+pub(crate) fn save(name: &str, messages: &[Message]) -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = session_path(name)?;
+    let content = serde_json::to_string_pretty(messages)?;
+    fs::write(history_path, content)?;
+    Ok(())
+}
+
+/// Lists the names of all sessions that have been saved to disk.
+pub(crate) fn list() -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let dir = sessions_dir()?;
+    let mut names = Vec::new();
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json") {
+            continue;
+        }
+        if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+            names.push(name.to_string());
+        }
+    }
+
+    names.sort();
+    Ok(names)
+}
+
+/// Deletes a saved session's history file, if it exists.
+pub(crate) fn remove(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let path = session_path(name)?;
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}